@@ -1,7 +1,751 @@
 // 10x12 Board https://www.chessprogramming.org/10x12_Board
 //A1 = 21  H8=98
 
-const BRD_SQ_NUM:i32 = 120;
+pub const BRD_SQ_NUM:usize = 120;
+
+pub const START_FEN:&str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+// castling permission bits
+pub const WKCA:u8 = 1;
+pub const WQCA:u8 = 2;
+pub const BKCA:u8 = 4;
+pub const BQCA:u8 = 8;
+
+const KN_DIR:[i32; 8] = [-8, -19, -21, -12, 8, 19, 21, 12];
+const RK_DIR:[i32; 4] = [-1, -10, 1, 10];
+const BI_DIR:[i32; 4] = [-9, -11, 11, 9];
+const KI_DIR:[i32; 8] = [-1, -10, 1, 10, -9, -11, 11, 9];
+
+// directions looked along from the attacked square, attacking kinds,
+// pawn color (pawns attack one way only), and whether the piece slides
+type Ray = (&'static [i32], &'static [PieceType], Option<Color>, bool);
+const ATTACK_RAYS:[Ray; 6] = [
+    (&[-9, -11], &[PieceType::Pawn], Some(Color::White), false),
+    (&[9, 11], &[PieceType::Pawn], Some(Color::Black), false),
+    (&KN_DIR, &[PieceType::Knight], None, false),
+    (&KI_DIR, &[PieceType::King], None, false),
+    (&RK_DIR, &[PieceType::Rook, PieceType::Queen], None, true),
+    (&BI_DIR, &[PieceType::Bishop, PieceType::Queen], None, true),
+];
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Color {
+    White,
+    Black,
+}
+
+impl Color {
+    pub fn opposite(self) -> Color {
+        match self {
+            Color::White => Color::Black,
+            Color::Black => Color::White,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum PieceType {
+    Pawn,
+    Knight,
+    Bishop,
+    Rook,
+    Queen,
+    King,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Piece {
+    pub color: Color,
+    pub kind: PieceType,
+}
+
+impl Piece {
+    pub fn new(color: Color, kind: PieceType) -> Piece {
+        Piece { color, kind }
+    }
+
+    // FEN letter, upper case for white
+    pub fn from_char(c: char) -> Option<Piece> {
+        let kind = match c.to_ascii_lowercase() {
+            'p' => PieceType::Pawn,
+            'n' => PieceType::Knight,
+            'b' => PieceType::Bishop,
+            'r' => PieceType::Rook,
+            'q' => PieceType::Queen,
+            'k' => PieceType::King,
+            _ => return None,
+        };
+        let color = if c.is_ascii_uppercase() { Color::White } else { Color::Black };
+        Some(Piece::new(color, kind))
+    }
+
+    pub fn to_char(self) -> char {
+        let c = match self.kind {
+            PieceType::Pawn => 'p',
+            PieceType::Knight => 'n',
+            PieceType::Bishop => 'b',
+            PieceType::Rook => 'r',
+            PieceType::Queen => 'q',
+            PieceType::King => 'k',
+        };
+        if self.color == Color::White { c.to_ascii_uppercase() } else { c }
+    }
+}
+
+// file and rank are 0..8, a1 = (0, 0)
+pub fn fr_to_sq(file: usize, rank: usize) -> usize {
+    21 + file + rank * 10
+}
+
+pub fn on_board(sq: usize) -> bool {
+    let file = sq % 10;
+    let rank = sq / 10;
+    (1..=8).contains(&file) && (2..=9).contains(&rank)
+}
+
+pub fn sq_rank(sq: usize) -> usize {
+    sq / 10 - 2
+}
+
+pub fn sq_file(sq: usize) -> usize {
+    sq % 10 - 1
+}
+
+// bit index for u64 square sets, a1 = 0 h8 = 63
+pub fn sq64(sq: usize) -> usize {
+    sq_rank(sq) * 8 + sq_file(sq)
+}
+
+// a square on the 10x12 board, must be on board
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Square(pub usize);
+
+impl Square {
+    // "a1".."h8"
+    pub fn from_algebraic(s: &str) -> Option<Square> {
+        let b = s.as_bytes();
+        if b.len() != 2 || !(b'a'..=b'h').contains(&b[0]) || !(b'1'..=b'8').contains(&b[1]) {
+            return None;
+        }
+        Some(Square(fr_to_sq((b[0] - b'a') as usize, (b[1] - b'1') as usize)))
+    }
+
+    pub fn to_algebraic(self) -> String {
+        debug_assert!(on_board(self.0), "square {} is off board", self.0);
+        let file = (b'a' + sq_file(self.0) as u8) as char;
+        let rank = (b'1' + sq_rank(self.0) as u8) as char;
+        format!("{}{}", file, rank)
+    }
+}
+
+impl std::fmt::Display for Square {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.to_algebraic())
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct Board {
+    pub pieces: [Option<Piece>; BRD_SQ_NUM],
+    pub side: Color,
+    pub en_pas: Option<usize>,
+    pub fifty_move: u32,
+    pub fullmove: u32,
+    pub castle_perm: u8,
+}
+
+impl Board {
+    pub fn empty() -> Board {
+        Board {
+            pieces: [None; BRD_SQ_NUM],
+            side: Color::White,
+            en_pas: None,
+            fifty_move: 0,
+            fullmove: 1,
+            castle_perm: 0,
+        }
+    }
+
+    pub fn from_fen(fen: &str) -> Result<Board, String> {
+        let parts: Vec<&str> = fen.split_whitespace().collect();
+        // halfmove and fullmove are often left off, default to 0 and 1
+        if !(4..=6).contains(&parts.len()) {
+            return Err(format!("FEN must have 4 to 6 fields, got {}", parts.len()));
+        }
+        let mut board = Board::empty();
+
+        // piece placement, rank 8 first
+        let ranks: Vec<&str> = parts[0].split('/').collect();
+        if ranks.len() != 8 {
+            return Err(format!("FEN must have 8 ranks, got {}", ranks.len()));
+        }
+        for (i, row) in ranks.iter().enumerate() {
+            let rank = 7 - i;
+            let mut file = 0;
+            let mut prev_digit = false;
+            for c in row.chars() {
+                if let Some(n) = c.to_digit(10) {
+                    // "0" and "44" describe the same squares as "" and "8"
+                    if n == 0 || prev_digit {
+                        return Err(format!("non-canonical empty count on rank {}", rank + 1));
+                    }
+                    file += n as usize;
+                    prev_digit = true;
+                } else {
+                    prev_digit = false;
+                    let piece = Piece::from_char(c).ok_or(format!("invalid piece '{}'", c))?;
+                    if file > 7 {
+                        return Err(format!("too many squares on rank {}", rank + 1));
+                    }
+                    board.set_piece(fr_to_sq(file, rank), piece);
+                    file += 1;
+                }
+            }
+            if file != 8 {
+                return Err(format!("rank {} does not have 8 squares", rank + 1));
+            }
+        }
+
+        board.side = match parts[1] {
+            "w" => Color::White,
+            "b" => Color::Black,
+            s => return Err(format!("invalid side to move '{}'", s)),
+        };
+
+        if parts[2] != "-" {
+            for c in parts[2].chars() {
+                let bit = match c {
+                    'K' => WKCA,
+                    'Q' => WQCA,
+                    'k' => BKCA,
+                    'q' => BQCA,
+                    _ => return Err(format!("invalid castling right '{}'", c)),
+                };
+                if board.castle_perm & bit != 0 {
+                    return Err(format!("duplicate castling right '{}'", c));
+                }
+                board.castle_perm |= bit;
+            }
+            // rights whose king or rook has left home can never be used
+            board.castle_perm &= board.possible_castle_perm();
+        }
+
+        if parts[3] != "-" {
+            let sq = Square::from_algebraic(parts[3]).ok_or(format!("invalid en passant square '{}'", parts[3]))?;
+            let rank = if board.side == Color::White { 5 } else { 2 };
+            if sq_rank(sq.0) != rank {
+                return Err(format!("en passant square '{}' must be on rank {}", parts[3], rank + 1));
+            }
+            board.en_pas = Some(sq.0);
+        }
+
+        if let Some(s) = parts.get(4) {
+            board.fifty_move = s.parse().map_err(|_| format!("invalid halfmove clock '{}'", s))?;
+        }
+        if let Some(s) = parts.get(5) {
+            board.fullmove = s.parse().map_err(|_| format!("invalid fullmove number '{}'", s))?;
+        }
+
+        board.validate()?;
+        Ok(board)
+    }
+
+    pub fn to_fen(&self) -> String {
+        let mut placement = String::new();
+        for rank in (0..8).rev() {
+            let mut empty = 0;
+            for file in 0..8 {
+                match self.pieces[fr_to_sq(file, rank)] {
+                    Some(p) => {
+                        if empty > 0 {
+                            placement.push_str(&empty.to_string());
+                            empty = 0;
+                        }
+                        placement.push(p.to_char());
+                    }
+                    None => empty += 1,
+                }
+            }
+            if empty > 0 {
+                placement.push_str(&empty.to_string());
+            }
+            if rank > 0 {
+                placement.push('/');
+            }
+        }
+
+        let side = if self.side == Color::White { "w" } else { "b" };
+
+        let perm = self.castle_perm & self.possible_castle_perm();
+        let mut castling: String = [(WKCA, 'K'), (WQCA, 'Q'), (BKCA, 'k'), (BQCA, 'q')]
+            .iter()
+            .filter(|(bit, _)| perm & bit != 0)
+            .map(|(_, c)| *c)
+            .collect();
+        if castling.is_empty() {
+            castling.push('-');
+        }
+
+        let en_pas = self.en_pas.map_or("-".to_string(), |sq| Square(sq).to_algebraic());
+
+        format!("{} {} {} {} {} {}", placement, side, castling, en_pas, self.fifty_move, self.fullmove)
+    }
+
+    // castling rights whose king and rook are still on their home squares
+    pub fn possible_castle_perm(&self) -> u8 {
+        let at = |file: usize, rank: usize, color: Color, kind: PieceType| {
+            self.pieces[fr_to_sq(file, rank)] == Some(Piece::new(color, kind))
+        };
+        let mut perm = 0;
+        for (color, rank, king_side, queen_side) in [(Color::White, 0, WKCA, WQCA), (Color::Black, 7, BKCA, BQCA)] {
+            if at(4, rank, color, PieceType::King) {
+                if at(7, rank, color, PieceType::Rook) {
+                    perm |= king_side;
+                }
+                if at(0, rank, color, PieceType::Rook) {
+                    perm |= queen_side;
+                }
+            }
+        }
+        perm
+    }
+
+    // rejects positions that can not arise in a legal game
+    pub fn validate(&self) -> Result<(), String> {
+        for color in [Color::White, Color::Black] {
+            let kings = self.count(Piece::new(color, PieceType::King));
+            if kings != 1 {
+                return Err(format!("{:?} must have exactly one king, found {}", color, kings));
+            }
+            let pawns = self.count(Piece::new(color, PieceType::Pawn));
+            if pawns > 8 {
+                return Err(format!("{:?} has {} pawns", color, pawns));
+            }
+        }
+        if self.has_illegal_pawns() {
+            return Err("pawn on rank 1 or 8".to_string());
+        }
+        let other = self.side.opposite();
+        if self.in_check(other) {
+            return Err(format!("{:?} is not to move but is in check", other));
+        }
+        Ok(())
+    }
+
+    // a pawn on rank 1 or 8, e.g. placed with set_piece
+    pub fn has_illegal_pawns(&self) -> bool {
+        (0..8).any(|file| {
+            [0, 7].iter().any(|&rank| {
+                matches!(self.pieces[fr_to_sq(file, rank)], Some(p) if p.kind == PieceType::Pawn)
+            })
+        })
+    }
+
+    // flips ranks and swaps colors, so white's position becomes black's
+    pub fn mirror(&self) -> Board {
+        let flip = |sq: usize| fr_to_sq(sq_file(sq), 7 - sq_rank(sq));
+        let mut board = Board::empty();
+        for sq in (0..BRD_SQ_NUM).filter(|&sq| on_board(sq)) {
+            if let Some(p) = self.pieces[sq] {
+                board.set_piece(flip(sq), Piece::new(p.color.opposite(), p.kind));
+            }
+        }
+        board.side = self.side.opposite();
+        board.en_pas = self.en_pas.map(flip);
+        board.fifty_move = self.fifty_move;
+        board.fullmove = self.fullmove;
+        board.castle_perm = ((self.castle_perm & (WKCA | WQCA)) << 2) | ((self.castle_perm & (BKCA | BQCA)) >> 2);
+        board
+    }
+
+    pub fn piece_at(&self, sq: usize) -> Option<Piece> {
+        self.pieces[sq]
+    }
+
+    pub fn set_piece(&mut self, sq: usize, piece: Piece) {
+        self.pieces[sq] = Some(piece);
+    }
+
+    pub fn clear_piece(&mut self, sq: usize) {
+        self.pieces[sq] = None;
+    }
+
+    pub fn count(&self, piece: Piece) -> usize {
+        self.pieces.iter().filter(|p| **p == Some(piece)).count()
+    }
+
+    // K v K, K+minor v K, or only bishops left all on one square color
+    pub fn is_insufficient_material(&self) -> bool {
+        let mut minors = 0;
+        let mut knights = 0;
+        let mut bishop_colors = [false; 2];
+        for sq in 0..BRD_SQ_NUM {
+            if let Some(p) = self.pieces[sq] {
+                match p.kind {
+                    PieceType::King => {}
+                    PieceType::Knight => {
+                        minors += 1;
+                        knights += 1;
+                    }
+                    PieceType::Bishop => {
+                        minors += 1;
+                        bishop_colors[(sq_file(sq) + sq_rank(sq)) % 2] = true;
+                    }
+                    _ => return false,
+                }
+            }
+        }
+        minors <= 1 || (knights == 0 && !(bishop_colors[0] && bishop_colors[1]))
+    }
+
+    // the double-pushed enemy pawn is in front of the en passant square and
+    // a pawn of the side to move stands next to it
+    pub fn en_pas_capturable(&self) -> bool {
+        let Some(ep) = self.en_pas.filter(|&ep| on_board(ep)) else { return false };
+        let pawn = Some(Piece::new(self.side, PieceType::Pawn));
+        let enemy_pawn = Some(Piece::new(self.side.opposite(), PieceType::Pawn));
+        let (pushed, from) = if self.side == Color::White {
+            (ep - 10, [ep - 9, ep - 11])
+        } else {
+            (ep + 10, [ep + 9, ep + 11])
+        };
+        self.pieces[pushed] == enemy_pawn && from.iter().any(|&sq| on_board(sq) && self.pieces[sq] == pawn)
+    }
+
+    pub fn king_square(&self, color: Color) -> Option<usize> {
+        let king = Some(Piece::new(color, PieceType::King));
+        (0..BRD_SQ_NUM).find(|&sq| self.pieces[sq] == king)
+    }
+
+    pub fn in_check(&self, color: Color) -> bool {
+        match self.king_square(color) {
+            Some(sq) => self.is_square_attacked(sq, color.opposite()),
+            None => false,
+        }
+    }
+
+    // is sq attacked by a piece of color `by`, stops at the first attacker
+    pub fn is_square_attacked(&self, sq: usize, by: Color) -> bool {
+        self.scan_attackers(sq, u64::MAX, Some(by)) != 0
+    }
+
+    // set of every occupied square, for attackers_to
+    pub fn occupancy(&self) -> u64 {
+        (0..BRD_SQ_NUM)
+            .filter(|&sq| on_board(sq) && self.pieces[sq].is_some())
+            .fold(0, |bb, sq| bb | 1 << sq64(sq))
+    }
+
+    // pieces of both colors attacking square, looking only at squares set in
+    // occupancy so SEE can take pieces off as it goes and see x-rays
+    pub fn attackers_to(&self, square: Square, occupancy: u64) -> u64 {
+        self.scan_attackers(square.0, occupancy, None)
+    }
+
+    // a square blocks only if it holds a piece and is set in occupancy.
+    // with a color given, returns as soon as one of its attackers is found
+    fn scan_attackers(&self, sq: usize, occupancy: u64, by: Option<Color>) -> u64 {
+        let mut attackers = 0;
+        for (dirs, kinds, pawn_color, slides) in ATTACK_RAYS {
+            for &d in dirs {
+                let mut t = (sq as i32 + d) as usize;
+                while on_board(t) {
+                    let piece = self.pieces[t].filter(|_| occupancy & 1 << sq64(t) != 0);
+                    if let Some(p) = piece {
+                        let color_ok = pawn_color.is_none_or(|c| c == p.color) && by.is_none_or(|c| c == p.color);
+                        if color_ok && kinds.contains(&p.kind) {
+                            attackers |= 1 << sq64(t);
+                            if by.is_some() {
+                                return attackers;
+                            }
+                        }
+                        break;
+                    }
+                    if !slides {
+                        break;
+                    }
+                    t = (t as i32 + d) as usize;
+                }
+            }
+        }
+        attackers
+    }
+
+    // given side at the bottom, so black sees rank 1 on top and h file on the left
+    pub fn to_string_perspective(&self, color: Color) -> String {
+        let ranks: Vec<usize> = if color == Color::White { (0..8).rev().collect() } else { (0..8).collect() };
+        let files: Vec<usize> = if color == Color::White { (0..8).collect() } else { (0..8).rev().collect() };
+        let mut s = String::new();
+        for &rank in &ranks {
+            s.push_str(&format!("{}  ", rank + 1));
+            for &file in &files {
+                let c = self.pieces[fr_to_sq(file, rank)].map_or('.', |p| p.to_char());
+                s.push_str(&format!(" {}", c));
+            }
+            s.push('\n');
+        }
+        s.push_str("\n   ");
+        for &file in &files {
+            s.push_str(&format!(" {}", (b'a' + file as u8) as char));
+        }
+        s.push('\n');
+        s
+    }
+}
+
+impl std::fmt::Display for Board {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.to_string_perspective(Color::White))
+    }
+}
+
 pub fn board() {
     println!("Hello, {0}", BRD_SQ_NUM);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn square_algebraic_round_trip() {
+        for rank in 0..8 {
+            for file in 0..8 {
+                let sq = Square(fr_to_sq(file, rank));
+                assert_eq!(Square::from_algebraic(&sq.to_algebraic()), Some(sq));
+            }
+        }
+        assert_eq!(Square(21).to_string(), "a1");
+        assert_eq!(Square(98).to_string(), "h8");
+    }
+
+    #[test]
+    fn square_rejects_out_of_range() {
+        for s in ["i9", "a0", "a9", "i1", "A1", "a", "a10", ""] {
+            assert_eq!(Square::from_algebraic(s), None, "{}", s);
+        }
+    }
+
+    #[test]
+    fn start_position_is_valid() {
+        assert!(Board::from_fen(START_FEN).is_ok());
+    }
+
+    #[test]
+    fn rejects_missing_king() {
+        assert!(Board::from_fen("8/8/8/8/8/8/8/K7 w - - 0 1").is_err());
+    }
+
+    #[test]
+    fn rejects_two_kings() {
+        assert!(Board::from_fen("KK6/8/8/8/8/8/8/7k w - - 0 1").is_err());
+    }
+
+    #[test]
+    fn rejects_nine_pawns() {
+        assert!(Board::from_fen("4k3/8/8/8/8/p7/pppppppp/4K3 w - - 0 1").is_err());
+    }
+
+    #[test]
+    fn rejects_pawn_on_back_rank() {
+        assert!(Board::from_fen("P6k/8/8/8/8/8/8/7K w - - 0 1").is_err());
+        assert!(Board::from_fen("7k/8/8/8/8/8/8/p6K w - - 0 1").is_err());
+    }
+
+    #[test]
+    fn rejects_side_not_to_move_in_check() {
+        assert!(Board::from_fen("k7/8/8/8/8/8/8/R6K w - - 0 1").is_err());
+        assert!(Board::from_fen("k7/8/1N6/8/8/8/8/7K w - - 0 1").is_err());
+        assert!(Board::from_fen("k7/8/8/8/8/8/6p1/7K b - - 0 1").is_err());
+    }
+
+    #[test]
+    fn accepts_side_to_move_in_check() {
+        assert!(Board::from_fen("k7/8/8/8/8/8/8/R6K b - - 0 1").is_ok());
+        assert!(Board::from_fen("k7/8/8/8/8/8/6p1/7K w - - 0 1").is_ok());
+    }
+
+    #[test]
+    fn boards_from_same_fen_are_equal() {
+        let kiwipete = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+        assert_eq!(Board::from_fen(kiwipete).unwrap(), Board::from_fen(kiwipete).unwrap());
+        assert_ne!(Board::from_fen(kiwipete).unwrap(), Board::from_fen(START_FEN).unwrap());
+
+        let mut seen = std::collections::HashMap::new();
+        seen.insert(Board::from_fen(START_FEN).unwrap(), 1);
+        *seen.entry(Board::from_fen(START_FEN).unwrap()).or_insert(0) += 1;
+        seen.insert(Board::from_fen(kiwipete).unwrap(), 1);
+        assert_eq!(seen.len(), 2);
+        assert_eq!(seen[&Board::from_fen(START_FEN).unwrap()], 2);
+    }
+
+    #[test]
+    fn mirror_twice_is_identity() {
+        for fen in [
+            START_FEN,
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "rnbqkbnr/ppp1pppp/8/8/3pP3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 3",
+        ] {
+            let board = Board::from_fen(fen).unwrap();
+            assert_eq!(board.mirror().mirror(), board);
+        }
+    }
+
+    #[test]
+    fn mirror_swaps_castling_and_en_passant() {
+        let board = Board::from_fen("r3k2r/pppbbppp/8/3pP3/8/8/PPPBBPPP/R3K2R w Kq d6 4 9").unwrap();
+        let expected = Board::from_fen("r3k2r/pppbbppp/8/8/3Pp3/8/PPPBBPPP/R3K2R b Qk d3 4 9").unwrap();
+        assert_eq!(board.mirror(), expected);
+    }
+
+    #[test]
+    fn black_perspective_shows_rank_one_on_top() {
+        let board = Board::from_fen(START_FEN).unwrap();
+        let black = board.to_string_perspective(Color::Black);
+        let lines: Vec<&str> = black.lines().collect();
+        assert_eq!(lines[0], "1   R N B K Q B N R");
+        assert_eq!(lines[7], "8   r n b k q b n r");
+        assert_eq!(lines[9], "    h g f e d c b a");
+
+        let white = board.to_string();
+        assert!(white.starts_with("8   r n b q k b n r"));
+    }
+
+    fn squares(bb: u64) -> Vec<String> {
+        (0..64)
+            .filter(|i| bb >> i & 1 == 1)
+            .map(|i| Square(fr_to_sq(i % 8, i / 8)).to_algebraic())
+            .collect()
+    }
+
+    #[test]
+    fn attackers_to_central_square() {
+        let board = Board::from_fen("7k/8/3p1n2/2r5/8/2N2B2/3P4/4RK2 w - - 0 1").unwrap();
+        let e4 = Square::from_algebraic("e4").unwrap();
+        assert_eq!(squares(board.attackers_to(e4, board.occupancy())), ["e1", "c3", "f3", "f6"]);
+
+        let board = Board::from_fen("4k3/8/8/3p4/4P3/8/8/4K3 w - - 0 1").unwrap();
+        let d5 = Square::from_algebraic("d5").unwrap();
+        let e4 = Square::from_algebraic("e4").unwrap();
+        assert_eq!(squares(board.attackers_to(d5, board.occupancy())), ["e4"]);
+        assert_eq!(squares(board.attackers_to(e4, board.occupancy())), ["d5"]);
+    }
+
+    #[test]
+    fn attackers_to_sees_x_rays() {
+        let board = Board::from_fen("4k3/4r3/4r3/8/8/8/4R3/4RK2 b - - 0 1").unwrap();
+        let e4 = Square::from_algebraic("e4").unwrap();
+        let occupancy = board.occupancy();
+        assert_eq!(squares(board.attackers_to(e4, occupancy)), ["e2", "e6"]);
+
+        let e2 = 1 << sq64(Square::from_algebraic("e2").unwrap().0);
+        let e6 = 1 << sq64(Square::from_algebraic("e6").unwrap().0);
+        assert_eq!(squares(board.attackers_to(e4, occupancy & !e2 & !e6)), ["e1", "e7"]);
+    }
+
+    #[test]
+    fn pawn_placed_on_rank_eight_is_flagged() {
+        let mut board = Board::from_fen(START_FEN).unwrap();
+        assert!(!board.has_illegal_pawns());
+        board.set_piece(Square::from_algebraic("a8").unwrap().0, Piece::new(Color::White, PieceType::Pawn));
+        assert!(board.has_illegal_pawns());
+    }
+
+    #[test]
+    fn four_field_fen_defaults_counters() {
+        let board = Board::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq -").unwrap();
+        assert_eq!(board.fifty_move, 0);
+        assert_eq!(board.fullmove, 1);
+        assert_eq!(board, Board::from_fen(START_FEN).unwrap());
+    }
+
+    #[test]
+    fn five_field_fen_defaults_fullmove() {
+        let board = Board::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 7").unwrap();
+        assert_eq!(board.fifty_move, 7);
+        assert_eq!(board.fullmove, 1);
+    }
+
+    #[test]
+    fn rejects_wrong_field_count() {
+        assert!(Board::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq").is_err());
+        assert!(Board::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1 x").is_err());
+    }
+
+    #[test]
+    fn rejects_duplicate_castling_rights() {
+        assert!(Board::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KKkq - 0 1").is_err());
+    }
+
+    #[test]
+    fn rejects_zero_and_consecutive_digits() {
+        assert!(Board::from_fen("rnbqkbnr/pppppppp/44/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1").is_err());
+        assert!(Board::from_fen("rnbqkbnr/pppppppp/0008/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1").is_err());
+        assert!(Board::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR0 w KQkq - 0 1").is_err());
+        assert!(Board::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNB0QKBNR w KQkq - 0 1").is_err());
+    }
+
+    #[test]
+    fn rejects_en_passant_on_wrong_rank() {
+        assert!(Board::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq e3 0 1").is_err());
+        assert!(Board::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq e4 0 1").is_err());
+    }
+
+    #[test]
+    fn drops_castling_rights_without_king_and_rook_at_home() {
+        let board = Board::from_fen("4k3/8/8/8/8/8/8/4K2R w Kkq -").unwrap();
+        assert_eq!(board.to_fen(), "4k3/8/8/8/8/8/8/4K2R w K - 0 1");
+    }
+
+    #[test]
+    fn fen_round_trip() {
+        for fen in [
+            START_FEN,
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+            "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+            "rnbqkbnr/ppp1pppp/8/8/3pP3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 3",
+            "rnbqkbnr/pppp1ppp/8/3Pp3/8/8/PPP1PPPP/RNBQKBNR w KQkq e6 0 3",
+        ] {
+            let board = Board::from_fen(fen).unwrap();
+            assert_eq!(board.to_fen(), fen);
+            assert_eq!(Board::from_fen(&board.to_fen()).unwrap(), board);
+        }
+    }
+
+    #[test]
+    fn insufficient_material() {
+        for fen in [
+            "8/8/8/8/8/8/8/K6k w - - 0 1",
+            "8/8/8/8/8/8/8/KN5k w - - 0 1",
+            "8/8/8/8/8/8/8/KB5k w - - 0 1",
+            "8/8/8/8/8/8/8/KB3b1k w - - 0 1",
+        ] {
+            assert!(Board::from_fen(fen).unwrap().is_insufficient_material(), "{}", fen);
+        }
+        for fen in [
+            "8/8/8/8/8/8/8/KNN4k w - - 0 1",
+            "8/8/8/8/8/8/8/KB4nk w - - 0 1",
+            "8/8/8/8/8/8/8/KB4bk w - - 0 1",
+            "8/8/8/8/8/8/P7/K6k w - - 0 1",
+            "8/8/8/8/8/8/8/KR5k b - - 0 1",
+            "8/8/8/8/8/8/8/KQ5k b - - 0 1",
+            START_FEN,
+        ] {
+            assert!(!Board::from_fen(fen).unwrap().is_insufficient_material(), "{}", fen);
+        }
+    }
+
+    #[test]
+    fn is_square_attacked_by_color_and_blocked() {
+        let board = Board::from_fen("4k3/4r3/4r3/8/8/8/4R3/4RK2 b - - 0 1").unwrap();
+        let e4 = Square::from_algebraic("e4").unwrap().0;
+        let e3 = Square::from_algebraic("e3").unwrap().0;
+        let a7 = Square::from_algebraic("a7").unwrap().0;
+        assert!(board.is_square_attacked(e4, Color::White));
+        assert!(board.is_square_attacked(e4, Color::Black));
+        assert!(board.is_square_attacked(e3, Color::White));
+        assert!(!board.is_square_attacked(a7, Color::White));
+        assert!(board.is_square_attacked(a7, Color::Black));
+    }
+}
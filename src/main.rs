@@ -1,7 +1,9 @@
-static NAME:&str = "prawn 0.1";
+pub const ENGINE_NAME:&str = concat!("prawn ", env!("CARGO_PKG_VERSION"));
+pub const ENGINE_AUTHOR:&str = env!("CARGO_PKG_AUTHORS");
 pub mod board;
+pub mod zobrist;
 
 fn main() {
-    println!("Hello, {0}", NAME);
+    println!("Hello, {0} by {1}", ENGINE_NAME, ENGINE_AUTHOR);
     board::board();
-}
\ No newline at end of file
+}
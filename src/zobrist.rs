@@ -0,0 +1,199 @@
+// Zobrist hashing https://www.chessprogramming.org/Zobrist_Hashing
+
+use crate::board::{on_board, sq64, sq_file, Board, Color, Piece, PieceType, BRD_SQ_NUM};
+
+const DEFAULT_SEED:u64 = 0x7072_6177_6e00_0001;
+
+// https://prng.di.unimi.it/splitmix64.c
+fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+fn piece_index(p: Piece) -> usize {
+    p.color as usize * 6 + p.kind as usize
+}
+
+pub struct ZobristHasher {
+    pieces: [[u64; 64]; 12],
+    castling: [u64; 16],
+    en_pas: [u64; 8],
+    side: u64,
+}
+
+impl ZobristHasher {
+    pub fn new() -> ZobristHasher {
+        ZobristHasher::from_seed(DEFAULT_SEED)
+    }
+
+    // same seed gives the same keys on every run and machine
+    pub fn from_seed(seed: u64) -> ZobristHasher {
+        let mut state = seed;
+        let mut hasher = ZobristHasher {
+            pieces: [[0; 64]; 12],
+            castling: [0; 16],
+            en_pas: [0; 8],
+            side: 0,
+        };
+        for table in hasher.pieces.iter_mut() {
+            for key in table.iter_mut() {
+                *key = splitmix64(&mut state);
+            }
+        }
+        for key in hasher.castling.iter_mut() {
+            *key = splitmix64(&mut state);
+        }
+        for key in hasher.en_pas.iter_mut() {
+            *key = splitmix64(&mut state);
+        }
+        hasher.side = splitmix64(&mut state);
+        hasher
+    }
+
+    pub fn hash_board(&self, board: &Board) -> u64 {
+        let mut hash = 0;
+        for sq in (0..BRD_SQ_NUM).filter(|&sq| on_board(sq)) {
+            if let Some(p) = board.pieces[sq] {
+                hash ^= self.pieces[piece_index(p)][sq64(sq)];
+            }
+        }
+        hash ^= self.castling[board.castle_perm as usize];
+        // only when it can be taken, otherwise equal positions hash apart
+        if let Some(sq) = board.en_pas.filter(|_| board.en_pas_capturable()) {
+            hash ^= self.en_pas[sq_file(sq)];
+        }
+        if board.side == Color::Black {
+            hash ^= self.side;
+        }
+        hash
+    }
+
+    // pawns of both colors only, for caching pawn structure
+    pub fn pawn_key(&self, board: &Board) -> u64 {
+        let mut hash = 0;
+        for sq in (0..BRD_SQ_NUM).filter(|&sq| on_board(sq)) {
+            if let Some(p) = board.pieces[sq] {
+                if p.kind == PieceType::Pawn {
+                    hash ^= self.pieces[piece_index(p)][sq64(sq)];
+                }
+            }
+        }
+        hash
+    }
+
+    // depends only on how many of each piece there are, e.g. KRPvKR
+    pub fn material_key(&self, board: &Board) -> u64 {
+        let mut counts = [0; 12];
+        for sq in (0..BRD_SQ_NUM).filter(|&sq| on_board(sq)) {
+            if let Some(p) = board.pieces[sq] {
+                counts[piece_index(p)] += 1;
+            }
+        }
+        let mut hash = 0;
+        for (i, &n) in counts.iter().enumerate() {
+            for key in &self.pieces[i][..n] {
+                hash ^= key;
+            }
+        }
+        hash
+    }
+}
+
+impl Default for ZobristHasher {
+    fn default() -> Self {
+        ZobristHasher::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::START_FEN;
+
+    fn board(fen: &str) -> Board {
+        Board::from_fen(fen).unwrap()
+    }
+
+    #[test]
+    fn same_seed_gives_same_hash() {
+        let start = board(START_FEN);
+        let kiwipete = board("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1");
+        let (a, b) = (ZobristHasher::from_seed(1), ZobristHasher::from_seed(1));
+        assert_eq!(a.hash_board(&start), b.hash_board(&start));
+        assert_eq!(a.hash_board(&kiwipete), b.hash_board(&kiwipete));
+        assert_eq!(ZobristHasher::new().hash_board(&start), ZobristHasher::default().hash_board(&start));
+    }
+
+    #[test]
+    fn different_seeds_give_different_hashes() {
+        let start = board(START_FEN);
+        assert_ne!(ZobristHasher::from_seed(1).hash_board(&start), ZobristHasher::from_seed(2).hash_board(&start));
+    }
+
+    #[test]
+    fn pawn_key_ignores_pieces() {
+        let z = ZobristHasher::new();
+        let start = board(START_FEN);
+        let knights_out = board("r1bqkbnr/pppppppp/2n5/8/8/5N2/PPPPPPPP/RNBQKB1R w KQkq - 2 2");
+        assert_eq!(z.pawn_key(&start), z.pawn_key(&knights_out));
+        assert_ne!(z.hash_board(&start), z.hash_board(&knights_out));
+    }
+
+    #[test]
+    fn pawn_key_changes_when_a_pawn_moves() {
+        let z = ZobristHasher::new();
+        let e4 = board("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1");
+        assert_ne!(z.pawn_key(&board(START_FEN)), z.pawn_key(&e4));
+    }
+
+    #[test]
+    fn uncapturable_en_passant_is_not_hashed() {
+        let z = ZobristHasher::new();
+        let with_ep = board("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1");
+        let without = board("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1");
+        assert_eq!(z.hash_board(&with_ep), z.hash_board(&without));
+
+        // a white pawn could take on d6, but no black pawn stands on d5
+        let with_ep = board("4k3/8/8/4P3/8/8/8/4K3 w - d6 0 1");
+        let without = board("4k3/8/8/4P3/8/8/8/4K3 w - - 0 1");
+        assert_eq!(z.hash_board(&with_ep), z.hash_board(&without));
+    }
+
+    #[test]
+    fn capturable_en_passant_is_hashed() {
+        let z = ZobristHasher::new();
+        let with_ep = board("rnbqkbnr/ppp1pppp/8/8/3pP3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1");
+        let without = board("rnbqkbnr/ppp1pppp/8/8/3pP3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1");
+        assert_ne!(z.hash_board(&with_ep), z.hash_board(&without));
+
+        let with_ep = board("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1");
+        let without = board("4k3/8/8/3pP3/8/8/8/4K3 w - - 0 1");
+        assert_ne!(z.hash_board(&with_ep), z.hash_board(&without));
+    }
+
+    #[test]
+    fn off_board_en_passant_is_not_capturable() {
+        let mut b = board(START_FEN);
+        b.en_pas = Some(3);
+        assert!(!b.en_pas_capturable());
+    }
+
+    #[test]
+    fn material_key_ignores_placement() {
+        let z = ZobristHasher::new();
+        let a = board("8/8/3k4/8/2R5/8/1P2r3/4K3 w - - 0 1");
+        let b = board("r7/4k3/8/8/8/6P1/8/R3K3 w - - 0 1");
+        assert_eq!(z.material_key(&a), z.material_key(&b));
+    }
+
+    #[test]
+    fn material_key_changes_with_an_extra_pawn() {
+        let z = ZobristHasher::new();
+        let a = board("8/8/3k4/8/2R5/8/1P2r3/4K3 w - - 0 1");
+        let b = board("8/8/3k4/8/2R5/8/1PP1r3/4K3 w - - 0 1");
+        assert_ne!(z.material_key(&a), z.material_key(&b));
+    }
+}